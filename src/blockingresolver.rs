@@ -0,0 +1,240 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use c_ares;
+
+use error::Error;
+use futureresolver::FutureResolver;
+use host::HostResults;
+use nameinfo::NameInfoResult;
+use resolver::{Options, SocketInterest};
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Drive `future` to completion on the current thread, parking it whenever the future isn't
+/// ready rather than spinning. This is all `BlockingResolver` needs from an executor - the
+/// query itself still completes on whichever thread drives the channel, same as for
+/// `FutureResolver`: the crate's background polling thread, or, for a resolver built with
+/// `with_options_for_manual_driving()`, the thread that calls `process_fd()`.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            ::std::task::Poll::Ready(output) => return output,
+            ::std::task::Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// `BlockingResolver` provides a synchronous lookup API - it blocks the calling thread until
+/// the query completes.
+///
+/// This is built on top of `FutureResolver` - see its documentation for more.
+pub struct BlockingResolver {
+    resolver: FutureResolver,
+}
+
+impl BlockingResolver {
+    /// Create a new `BlockingResolver`, using default `Options`.
+    pub fn new() -> Result<BlockingResolver, Error> {
+        let resolver = FutureResolver::new()?;
+        Ok(BlockingResolver { resolver })
+    }
+
+    /// Create a new `BlockingResolver`, with the given `Options`.
+    pub fn with_options(options: Options) -> Result<BlockingResolver, Error> {
+        let resolver = FutureResolver::with_options(options)?;
+        Ok(BlockingResolver { resolver })
+    }
+
+    /// Create a new `BlockingResolver`, with the given `Options`, without spawning a background
+    /// thread to drive it.
+    ///
+    /// See `FutureResolver::with_options_for_manual_driving()`, which this is built on.
+    pub fn with_options_for_manual_driving(options: Options) -> Result<BlockingResolver, Error> {
+        let resolver = FutureResolver::with_options_for_manual_driving(options)?;
+        Ok(BlockingResolver { resolver })
+    }
+
+    /// Return the file descriptors that the underlying `c-ares` channel currently wants to be
+    /// polled on, along with the interest (readable, writable, or both) in each.
+    ///
+    /// Only meaningful for a `BlockingResolver` built with `with_options_for_manual_driving()`.
+    pub fn fds(&self) -> Vec<SocketInterest> {
+        self.resolver.fds()
+    }
+
+    /// Tell the channel that the given file descriptors are readable and/or writable.
+    ///
+    /// Pass `c_ares::SOCKET_BAD` for whichever of `read_fd`/`write_fd` isn't ready. Only
+    /// meaningful for a `BlockingResolver` built with `with_options_for_manual_driving()`.
+    pub fn process_fd(&self, read_fd: c_ares::Socket, write_fd: c_ares::Socket) {
+        self.resolver.process_fd(read_fd, write_fd)
+    }
+
+    /// Return how long the caller should wait, at most, before calling `process_fd()` again
+    /// even if none of the descriptors from `fds()` have become ready.
+    ///
+    /// Only meaningful for a `BlockingResolver` built with `with_options_for_manual_driving()` -
+    /// see `Resolver::timeout()`.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.resolver.timeout()
+    }
+
+    /// Look up the A records for `name`, blocking until the answer arrives.
+    pub fn query_a(&self, name: &str) -> Result<c_ares::AResults, Error> {
+        block_on(self.resolver.query_a(name))
+    }
+
+    /// As with `query_a()`, but blocking until either the answer arrives or `timeout` elapses,
+    /// whichever is first.
+    pub fn query_a_timeout(&self, name: &str, timeout: Duration) -> Result<c_ares::AResults, Error> {
+        block_on(self.resolver.query_a_timeout(name, timeout))
+    }
+
+    /// Look up the AAAA records for `name`, blocking until the answer arrives.
+    pub fn query_aaaa(&self, name: &str) -> Result<c_ares::AAAAResults, Error> {
+        block_on(self.resolver.query_aaaa(name))
+    }
+
+    /// As with `query_aaaa()`, but blocking until either the answer arrives or `timeout`
+    /// elapses, whichever is first.
+    pub fn query_aaaa_timeout(&self, name: &str, timeout: Duration) -> Result<c_ares::AAAAResults, Error> {
+        block_on(self.resolver.query_aaaa_timeout(name, timeout))
+    }
+
+    /// Look up the CNAME records for `name`, blocking until the answer arrives.
+    pub fn query_cname(&self, name: &str) -> Result<c_ares::CNameResults, Error> {
+        block_on(self.resolver.query_cname(name))
+    }
+
+    /// As with `query_cname()`, but blocking until either the answer arrives or `timeout`
+    /// elapses, whichever is first.
+    pub fn query_cname_timeout(&self, name: &str, timeout: Duration) -> Result<c_ares::CNameResults, Error> {
+        block_on(self.resolver.query_cname_timeout(name, timeout))
+    }
+
+    /// Look up the MX records for `name`, blocking until the answer arrives.
+    pub fn query_mx(&self, name: &str) -> Result<c_ares::MXResults, Error> {
+        block_on(self.resolver.query_mx(name))
+    }
+
+    /// As with `query_mx()`, but blocking until either the answer arrives or `timeout` elapses,
+    /// whichever is first.
+    pub fn query_mx_timeout(&self, name: &str, timeout: Duration) -> Result<c_ares::MXResults, Error> {
+        block_on(self.resolver.query_mx_timeout(name, timeout))
+    }
+
+    /// Look up the PTR records for `name`, blocking until the answer arrives.
+    pub fn query_ptr(&self, name: &str) -> Result<c_ares::PTRResults, Error> {
+        block_on(self.resolver.query_ptr(name))
+    }
+
+    /// As with `query_ptr()`, but blocking until either the answer arrives or `timeout` elapses,
+    /// whichever is first.
+    pub fn query_ptr_timeout(&self, name: &str, timeout: Duration) -> Result<c_ares::PTRResults, Error> {
+        block_on(self.resolver.query_ptr_timeout(name, timeout))
+    }
+
+    /// Look up the TXT records for `name`, blocking until the answer arrives.
+    pub fn query_txt(&self, name: &str) -> Result<c_ares::TXTResults, Error> {
+        block_on(self.resolver.query_txt(name))
+    }
+
+    /// As with `query_txt()`, but blocking until either the answer arrives or `timeout`
+    /// elapses, whichever is first.
+    pub fn query_txt_timeout(&self, name: &str, timeout: Duration) -> Result<c_ares::TXTResults, Error> {
+        block_on(self.resolver.query_txt_timeout(name, timeout))
+    }
+
+    /// Make a raw DNS query for `name`, blocking until the answer arrives.
+    ///
+    /// See `Resolver::query_raw()` for why this is useful.
+    pub fn query_raw(
+        &self,
+        name: &str,
+        dns_class: c_ares::DNSClass,
+        query_type: c_ares::QueryType,
+    ) -> Result<Vec<u8>, Error> {
+        block_on(self.resolver.query_raw(name, dns_class, query_type))
+    }
+
+    /// As with `query_raw()`, but blocking until either the answer arrives or `timeout`
+    /// elapses, whichever is first.
+    pub fn query_raw_timeout(
+        &self,
+        name: &str,
+        dns_class: c_ares::DNSClass,
+        query_type: c_ares::QueryType,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, Error> {
+        block_on(self.resolver.query_raw_timeout(name, dns_class, query_type, timeout))
+    }
+
+    /// As with `query_raw()`, but makes a call to `ares_search()` rather than `ares_query()`.
+    pub fn search_raw(
+        &self,
+        name: &str,
+        dns_class: c_ares::DNSClass,
+        query_type: c_ares::QueryType,
+    ) -> Result<Vec<u8>, Error> {
+        block_on(self.resolver.search_raw(name, dns_class, query_type))
+    }
+
+    /// As with `search_raw()`, but blocking until either the answer arrives or `timeout`
+    /// elapses, whichever is first.
+    pub fn search_raw_timeout(
+        &self,
+        name: &str,
+        dns_class: c_ares::DNSClass,
+        query_type: c_ares::QueryType,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, Error> {
+        block_on(self.resolver.search_raw_timeout(name, dns_class, query_type, timeout))
+    }
+
+    /// Look up the A and AAAA records for the given host name, blocking until the answer
+    /// arrives.
+    pub fn get_host_by_name(&self, name: &str, family: c_ares::AddressFamily) -> Result<HostResults, Error> {
+        block_on(self.resolver.get_host_by_name(name, family))
+    }
+
+    /// As with `get_host_by_name()`, but blocking until either the answer arrives or `timeout`
+    /// elapses, whichever is first.
+    pub fn get_host_by_name_timeout(
+        &self,
+        name: &str,
+        family: c_ares::AddressFamily,
+        timeout: Duration,
+    ) -> Result<HostResults, Error> {
+        block_on(self.resolver.get_host_by_name_timeout(name, family, timeout))
+    }
+
+    /// Address-to-nodename translation, as per RFC 3493, section 6.2, blocking until the answer
+    /// arrives.
+    pub fn get_name_info(&self, address: ::std::net::SocketAddr, flags: i32) -> Result<NameInfoResult, Error> {
+        block_on(self.resolver.get_name_info(address, flags))
+    }
+
+    /// As with `get_name_info()`, but blocking until either the answer arrives or `timeout`
+    /// elapses, whichever is first.
+    pub fn get_name_info_timeout(
+        &self,
+        address: ::std::net::SocketAddr,
+        flags: i32,
+        timeout: Duration,
+    ) -> Result<NameInfoResult, Error> {
+        block_on(self.resolver.get_name_info_timeout(address, flags, timeout))
+    }
+}
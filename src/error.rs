@@ -0,0 +1,38 @@
+use std::error;
+use std::fmt;
+
+use c_ares;
+
+/// An error that occurred while making a DNS query.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// A `c-ares` library error.
+    CAresError(c_ares::Error),
+
+    /// The query did not complete within the deadline given to e.g. `query_a_timeout()`.
+    Timeout,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::CAresError(ref e) => write!(f, "c-ares error: {}", e),
+            Error::Timeout => write!(f, "query timed out"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::CAresError(ref e) => e.description(),
+            Error::Timeout => "query timed out",
+        }
+    }
+}
+
+impl From<c_ares::Error> for Error {
+    fn from(error: c_ares::Error) -> Error {
+        Error::CAresError(error)
+    }
+}
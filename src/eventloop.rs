@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use c_ares;
+use mio;
+
+#[cfg(unix)]
+use unix as platform;
+
+#[cfg(windows)]
+use windows as platform;
+
+const CARES_TOKEN_BASE: usize = 1;
+const WAKEUP_TOKEN: mio::Token = mio::Token(0);
+
+/// Runs a background thread that polls the file descriptors that the `c-ares` channel is
+/// interested in, and calls back into the channel whenever one of them becomes readable or
+/// writable.
+///
+/// This is the machinery that lets `Resolver` and friends present a callback-based API without
+/// requiring the caller to run their own event loop. It is built directly on `mio`'s own
+/// `Registration`/`SetReadiness` pair, rather than on `mio_extras`, so that it has no dependency
+/// beyond `mio` itself.
+///
+/// On Windows, `mio` cannot deliver real readiness notifications for externally-owned sockets,
+/// so this falls back to polling them directly with `WSAPoll()` instead - see `windows.rs` for
+/// the details.
+pub struct EventLoop {
+    readiness: mio::SetReadiness,
+    shutdown: Arc<AtomicBool>,
+    join_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl EventLoop {
+    pub fn new(channel: Arc<Mutex<c_ares::Channel>>) -> io::Result<EventLoop> {
+        let poll = mio::Poll::new()?;
+        let (registration, readiness) = mio::Registration::new2();
+        poll.register(
+            &registration,
+            WAKEUP_TOKEN,
+            mio::Ready::readable(),
+            mio::PollOpt::edge(),
+        )?;
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_shutdown = Arc::clone(&shutdown);
+        let join_handle = thread::Builder::new()
+            .name("c-ares-resolver".to_owned())
+            .spawn(move || {
+                run(channel, poll, registration, thread_shutdown);
+            })?;
+
+        let event_loop = EventLoop {
+            readiness,
+            shutdown,
+            join_handle: Some(join_handle),
+        };
+        Ok(event_loop)
+    }
+
+    /// Wake the background thread up - used after submitting a new query, since that may
+    /// change the set of file descriptors that we need to be polling.
+    pub fn wakeup(&self) {
+        let _ = self.readiness.set_readiness(mio::Ready::readable());
+    }
+}
+
+impl Drop for EventLoop {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.wakeup();
+        if let Some(handle) = self.join_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run(
+    channel: Arc<Mutex<c_ares::Channel>>,
+    poll: mio::Poll,
+    registration: mio::Registration,
+    shutdown: Arc<AtomicBool>,
+) {
+    let mut registrations: HashMap<platform::Socket, mio::Token> = HashMap::new();
+    let mut events = mio::Events::with_capacity(32);
+    loop {
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let channel_timeout = {
+            let locked_channel = channel.lock().unwrap();
+            locked_channel.timeout().unwrap_or_else(|| Duration::from_millis(500))
+        };
+        poll.poll(&mut events, Some(platform::poll_timeout(channel_timeout))).unwrap();
+
+        // Readiness events for the sockets `c-ares` itself cares about, with the internal
+        // wakeup token filtered out, as `(token - CARES_TOKEN_BASE, readable, writable)` - on
+        // Unix, where tokens are `CARES_TOKEN_BASE + fd`, that is the fd. On Unix these come
+        // from real `mio` notifications; on
+        // Windows, where `mio` can't watch externally-owned sockets, `platform::drive()` below
+        // ignores this (always empty) list and does its own polling instead - see `windows.rs`.
+        let activity: Vec<(usize, bool, bool)> = events
+            .iter()
+            .filter(|event| event.token() != WAKEUP_TOKEN)
+            .map(|event| {
+                (
+                    event.token().0 - CARES_TOKEN_BASE,
+                    event.readiness().is_readable(),
+                    event.readiness().is_writable(),
+                )
+            })
+            .collect();
+
+        let locked_channel = channel.lock().unwrap();
+        platform::drive(&locked_channel, &activity, channel_timeout);
+
+        // The set of sockets that `c-ares` cares about may have changed as a result of any
+        // processing above, or of a new query having been submitted - bring our registrations
+        // up to date.
+        platform::refresh_registrations(&poll, &locked_channel, &mut registrations, CARES_TOKEN_BASE);
+    }
+    let _ = registration;
+}
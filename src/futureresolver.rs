@@ -0,0 +1,483 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use c_ares;
+use futures::stream::{self, Stream, StreamExt};
+
+use error::Error;
+use host::HostResults;
+use nameinfo::NameInfoResult;
+use resolver::{Options, Resolver, SocketInterest};
+
+struct SharedState<T> {
+    result: Option<Result<T, Error>>,
+    completed: bool,
+    waker: Option<Waker>,
+    /// The `spawn_timeout()` thread parked waiting for this query, if any - woken up as soon as
+    /// `result` is filled in so it doesn't sit parked for the rest of its timeout window.
+    timeout_thread: Option<Thread>,
+}
+
+/// A `Future` representing the result of a DNS query made through a `FutureResolver`.
+///
+/// This implements `std::future::Future`, so it can be `.await`ed directly on whatever async
+/// runtime the caller is already using - there's nothing `c-ares`-specific required of the
+/// executor.
+///
+/// Dropping a `CAresFuture` does *not* cancel the underlying query - `c-ares` only supports
+/// cancelling every query outstanding on a channel at once, so doing that automatically on
+/// every drop would cancel unrelated queries sharing the same `Resolver`/`FutureResolver`.
+/// If you do want to give up on a query (for example, one that you've decided to treat as
+/// abandoned after `query_a_timeout()`), and you know no other query is outstanding on the
+/// same resolver, call `cancel()` explicitly.
+pub struct CAresFuture<T> {
+    shared_state: Arc<Mutex<SharedState<T>>>,
+    resolver: Arc<Resolver>,
+}
+
+impl<T> CAresFuture<T> {
+    /// Cancel every query outstanding on the underlying resolver's channel.
+    ///
+    /// `c-ares` has no way to cancel a single query in isolation - see `Resolver::cancel()` -
+    /// so this is an explicit, deliberate action for the caller to take, not something this
+    /// future does automatically. Calling it while other queries are in flight on the same
+    /// `Resolver`/`FutureResolver` cancels those too. Calling it after this future has already
+    /// completed is a no-op.
+    pub fn cancel(&self) {
+        if !self.shared_state.lock().unwrap().completed {
+            self.resolver.cancel();
+        }
+    }
+}
+
+impl<T> Future for CAresFuture<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut shared_state = self.shared_state.lock().unwrap();
+        match shared_state.result.take() {
+            Some(result) => {
+                shared_state.completed = true;
+                Poll::Ready(result)
+            }
+            None => {
+                shared_state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn new_future<T>(resolver: Arc<Resolver>) -> (CAresFuture<T>, impl FnOnce(Result<T, Error>)) {
+    let shared_state = Arc::new(Mutex::new(SharedState {
+        result: None,
+        completed: false,
+        waker: None,
+        timeout_thread: None,
+    }));
+    let future = CAresFuture {
+        shared_state: Arc::clone(&shared_state),
+        resolver,
+    };
+    let complete = move |result| {
+        let (waker, timeout_thread) = {
+            let mut shared_state = shared_state.lock().unwrap();
+            shared_state.result = Some(result);
+            shared_state.completed = true;
+            (shared_state.waker.take(), shared_state.timeout_thread.take())
+        };
+        if let Some(timeout_thread) = timeout_thread {
+            timeout_thread.unpark();
+        }
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    };
+    (future, complete)
+}
+
+/// Like `new_future()`, but resolves to `Error::Timeout` itself if `timeout` elapses first.
+///
+/// The wait happens on a dedicated thread, parked rather than sleeping, so that a query which
+/// completes (or is cancelled) before `timeout` elapses wakes the thread up immediately instead
+/// of leaving it parked for the rest of the window.
+fn new_future_with_timeout<T: Send + 'static>(
+    resolver: Arc<Resolver>,
+    timeout: Duration,
+) -> (CAresFuture<T>, impl FnOnce(Result<T, Error>)) {
+    let (future, complete) = new_future(resolver);
+    spawn_timeout(Arc::clone(&future.shared_state), timeout);
+    (future, complete)
+}
+
+fn spawn_timeout<T: Send + 'static>(shared_state: Arc<Mutex<SharedState<T>>>, timeout: Duration) {
+    thread::spawn(move || {
+        {
+            let mut shared_state = shared_state.lock().unwrap();
+            if shared_state.result.is_some() {
+                return;
+            }
+            shared_state.timeout_thread = Some(thread::current());
+        }
+        thread::park_timeout(timeout);
+        let waker = {
+            let mut shared_state = shared_state.lock().unwrap();
+            if shared_state.result.is_some() {
+                return;
+            }
+            shared_state.result = Some(Err(Error::Timeout));
+            shared_state.timeout_thread = None;
+            shared_state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    });
+}
+
+/// `FutureResolver` provides a `Future`-based lookup API.
+///
+/// This is built on top of `Resolver` - see its documentation for more.
+pub struct FutureResolver {
+    resolver: Arc<Resolver>,
+}
+
+impl FutureResolver {
+    /// Create a new `FutureResolver`, using default `Options`.
+    pub fn new() -> Result<FutureResolver, Error> {
+        let resolver = Resolver::new()?;
+        Ok(FutureResolver {
+            resolver: Arc::new(resolver),
+        })
+    }
+
+    /// Create a new `FutureResolver`, with the given `Options`.
+    pub fn with_options(options: Options) -> Result<FutureResolver, Error> {
+        let resolver = Resolver::with_options(options)?;
+        Ok(FutureResolver {
+            resolver: Arc::new(resolver),
+        })
+    }
+
+    /// Create a new `FutureResolver`, with the given `Options`, without spawning a background
+    /// thread to drive it.
+    ///
+    /// Use this when you'd rather embed the `c-ares` channel in an event loop you already run
+    /// yourself - see `Resolver::with_options_for_manual_driving()`, which this is built on.
+    /// Register a callback with `Options::set_sock_state_callback()` beforehand to learn when
+    /// the set of interesting file descriptors changes, call `fds()` to find out what they
+    /// currently are, poll them yourself, and call `process_fd()` to report back when they
+    /// become readable or writable.
+    pub fn with_options_for_manual_driving(options: Options) -> Result<FutureResolver, Error> {
+        let resolver = Resolver::with_options_for_manual_driving(options)?;
+        Ok(FutureResolver {
+            resolver: Arc::new(resolver),
+        })
+    }
+
+    /// Return the file descriptors that the underlying `c-ares` channel currently wants to be
+    /// polled on, along with the interest (readable, writable, or both) in each.
+    ///
+    /// Only meaningful for a `FutureResolver` built with `with_options_for_manual_driving()`.
+    pub fn fds(&self) -> Vec<SocketInterest> {
+        self.resolver.fds()
+    }
+
+    /// Tell the channel that the given file descriptors are readable and/or writable.
+    ///
+    /// Pass `c_ares::SOCKET_BAD` for whichever of `read_fd`/`write_fd` isn't ready. Only
+    /// meaningful for a `FutureResolver` built with `with_options_for_manual_driving()`.
+    pub fn process_fd(&self, read_fd: c_ares::Socket, write_fd: c_ares::Socket) {
+        self.resolver.process_fd(read_fd, write_fd)
+    }
+
+    /// Return how long the caller should wait, at most, before calling `process_fd()` again
+    /// even if none of the descriptors from `fds()` have become ready.
+    ///
+    /// Only meaningful for a `FutureResolver` built with `with_options_for_manual_driving()` -
+    /// see `Resolver::timeout()`.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.resolver.timeout()
+    }
+
+    /// Initiate an A record lookup.
+    pub fn query_a(&self, name: &str) -> CAresFuture<c_ares::AResults> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.query_a(name, complete);
+        future
+    }
+
+    /// As with `query_a()`, but resolving to `Error::Timeout` if no answer has arrived within
+    /// `timeout`.
+    pub fn query_a_timeout(&self, name: &str, timeout: Duration) -> CAresFuture<c_ares::AResults> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.query_a(name, complete);
+        future
+    }
+
+    /// Initiate an AAAA record lookup.
+    pub fn query_aaaa(&self, name: &str) -> CAresFuture<c_ares::AAAAResults> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.query_aaaa(name, complete);
+        future
+    }
+
+    /// As with `query_aaaa()`, but resolving to `Error::Timeout` if no answer has arrived within
+    /// `timeout`.
+    pub fn query_aaaa_timeout(&self, name: &str, timeout: Duration) -> CAresFuture<c_ares::AAAAResults> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.query_aaaa(name, complete);
+        future
+    }
+
+    /// Initiate a CNAME record lookup.
+    pub fn query_cname(&self, name: &str) -> CAresFuture<c_ares::CNameResults> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.query_cname(name, complete);
+        future
+    }
+
+    /// As with `query_cname()`, but resolving to `Error::Timeout` if no answer has arrived
+    /// within `timeout`.
+    pub fn query_cname_timeout(&self, name: &str, timeout: Duration) -> CAresFuture<c_ares::CNameResults> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.query_cname(name, complete);
+        future
+    }
+
+    /// Initiate an MX record lookup.
+    pub fn query_mx(&self, name: &str) -> CAresFuture<c_ares::MXResults> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.query_mx(name, complete);
+        future
+    }
+
+    /// As with `query_mx()`, but resolving to `Error::Timeout` if no answer has arrived within
+    /// `timeout`.
+    pub fn query_mx_timeout(&self, name: &str, timeout: Duration) -> CAresFuture<c_ares::MXResults> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.query_mx(name, complete);
+        future
+    }
+
+    /// Initiate a PTR record lookup.
+    pub fn query_ptr(&self, name: &str) -> CAresFuture<c_ares::PTRResults> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.query_ptr(name, complete);
+        future
+    }
+
+    /// As with `query_ptr()`, but resolving to `Error::Timeout` if no answer has arrived within
+    /// `timeout`.
+    pub fn query_ptr_timeout(&self, name: &str, timeout: Duration) -> CAresFuture<c_ares::PTRResults> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.query_ptr(name, complete);
+        future
+    }
+
+    /// Initiate a TXT record lookup.
+    pub fn query_txt(&self, name: &str) -> CAresFuture<c_ares::TXTResults> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.query_txt(name, complete);
+        future
+    }
+
+    /// As with `query_txt()`, but resolving to `Error::Timeout` if no answer has arrived within
+    /// `timeout`.
+    pub fn query_txt_timeout(&self, name: &str, timeout: Duration) -> CAresFuture<c_ares::TXTResults> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.query_txt(name, complete);
+        future
+    }
+
+    /// Initiate a raw DNS query for `name`, for the given `dns_class` and `query_type`.
+    ///
+    /// See `Resolver::query_raw()` for why this is useful.
+    pub fn query_raw(
+        &self,
+        name: &str,
+        dns_class: c_ares::DNSClass,
+        query_type: c_ares::QueryType,
+    ) -> CAresFuture<Vec<u8>> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.query_raw(name, dns_class, query_type, complete);
+        future
+    }
+
+    /// As with `query_raw()`, but resolving to `Error::Timeout` if no answer has arrived within
+    /// `timeout`.
+    pub fn query_raw_timeout(
+        &self,
+        name: &str,
+        dns_class: c_ares::DNSClass,
+        query_type: c_ares::QueryType,
+        timeout: Duration,
+    ) -> CAresFuture<Vec<u8>> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.query_raw(name, dns_class, query_type, complete);
+        future
+    }
+
+    /// As with `query_raw()`, but makes a call to `ares_search()` rather than `ares_query()`.
+    pub fn search_raw(
+        &self,
+        name: &str,
+        dns_class: c_ares::DNSClass,
+        query_type: c_ares::QueryType,
+    ) -> CAresFuture<Vec<u8>> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.search_raw(name, dns_class, query_type, complete);
+        future
+    }
+
+    /// As with `search_raw()`, but resolving to `Error::Timeout` if no answer has arrived within
+    /// `timeout`.
+    pub fn search_raw_timeout(
+        &self,
+        name: &str,
+        dns_class: c_ares::DNSClass,
+        query_type: c_ares::QueryType,
+        timeout: Duration,
+    ) -> CAresFuture<Vec<u8>> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.search_raw(name, dns_class, query_type, complete);
+        future
+    }
+
+    /// Look up the A and AAAA records for the given host name.
+    pub fn get_host_by_name(&self, name: &str, family: c_ares::AddressFamily) -> CAresFuture<HostResults> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.get_host_by_name(name, family, complete);
+        future
+    }
+
+    /// As with `get_host_by_name()`, but resolving to `Error::Timeout` if no answer has arrived
+    /// within `timeout`.
+    pub fn get_host_by_name_timeout(
+        &self,
+        name: &str,
+        family: c_ares::AddressFamily,
+        timeout: Duration,
+    ) -> CAresFuture<HostResults> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.get_host_by_name(name, family, complete);
+        future
+    }
+
+    /// Address-to-nodename translation, as per RFC 3493, section 6.2.
+    pub fn get_name_info(&self, address: ::std::net::SocketAddr, flags: i32) -> CAresFuture<NameInfoResult> {
+        let (future, complete) = new_future(Arc::clone(&self.resolver));
+        self.resolver.get_name_info(address, flags, complete);
+        future
+    }
+
+    /// As with `get_name_info()`, but resolving to `Error::Timeout` if no answer has arrived
+    /// within `timeout`.
+    pub fn get_name_info_timeout(
+        &self,
+        address: ::std::net::SocketAddr,
+        flags: i32,
+        timeout: Duration,
+    ) -> CAresFuture<NameInfoResult> {
+        let (future, complete) = new_future_with_timeout(Arc::clone(&self.resolver), timeout);
+        self.resolver.get_name_info(address, flags, complete);
+        future
+    }
+
+    /// Submit an A-record query for every name in `names`, at most `max_in_flight` at a time,
+    /// and stream back each `(name, result)` pair as its answer arrives.
+    ///
+    /// All of the queries share the single underlying `c-ares` channel that every other method
+    /// on this resolver uses, and each is submitted the same way `query_a()` submits one. Unlike
+    /// a bare `join_all` over `query_a()` futures, this gives natural back-pressure via
+    /// `max_in_flight`. Pass `None` to submit every query as soon as the stream is first polled.
+    ///
+    /// `max_in_flight` must be at least 1 if given - `Some(0)` would mean never polling any
+    /// query, so it's treated the same as `Some(1)` rather than producing a stream that hangs
+    /// forever.
+    pub fn query_a_many<I>(
+        &self,
+        names: I,
+        max_in_flight: Option<usize>,
+    ) -> impl Stream<Item = (String, Result<c_ares::AResults, Error>)>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let resolver = Arc::clone(&self.resolver);
+        let queries = names.into_iter().map(move |name| {
+            let resolver = Arc::clone(&resolver);
+            async move {
+                let (future, complete) = new_future(Arc::clone(&resolver));
+                resolver.query_a(&name, complete);
+                let result = future.await;
+                (name, result)
+            }
+        });
+        stream::iter(queries).buffer_unordered(effective_concurrency(max_in_flight))
+    }
+}
+
+/// `max_in_flight` clamped to at least 1 - `Some(0)` would mean `buffer_unordered()` never polls
+/// anything, hanging the stream forever, so it's treated the same as `Some(1)`.
+fn effective_concurrency(max_in_flight: Option<usize>) -> usize {
+    max_in_flight.unwrap_or(usize::max_value()).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_concurrency_clamps_zero_to_one() {
+        assert_eq!(effective_concurrency(Some(0)), 1);
+        assert_eq!(effective_concurrency(Some(1)), 1);
+        assert_eq!(effective_concurrency(Some(4)), 4);
+        assert_eq!(effective_concurrency(None), usize::max_value());
+    }
+
+    #[test]
+    fn cancel_is_a_no_op_once_completed() {
+        let resolver = Arc::new(Resolver::new().unwrap());
+        let (future, complete) = new_future::<()>(Arc::clone(&resolver));
+        complete(Ok(()));
+
+        assert!(future.shared_state.lock().unwrap().completed);
+        // If `cancel()` didn't honour the `completed` guard, this would reach
+        // `Resolver::cancel()` and cancel every other query sharing this channel.
+        future.cancel();
+    }
+
+    #[test]
+    fn spawn_timeout_resolves_to_timeout_if_never_completed() {
+        let resolver = Arc::new(Resolver::new().unwrap());
+        let (mut future, _complete) = new_future::<()>(Arc::clone(&resolver));
+        spawn_timeout(Arc::clone(&future.shared_state), Duration::from_millis(10));
+        thread::sleep(Duration::from_millis(100));
+
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        match Pin::new(&mut future).poll(&mut cx) {
+            Poll::Ready(Err(Error::Timeout)) => {}
+            other => panic!("expected Poll::Ready(Err(Error::Timeout)), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn spawn_timeout_does_not_override_a_result_that_arrives_first() {
+        let resolver = Arc::new(Resolver::new().unwrap());
+        let (mut future, complete) = new_future::<u32>(Arc::clone(&resolver));
+        spawn_timeout(Arc::clone(&future.shared_state), Duration::from_millis(100));
+        complete(Ok(42));
+
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        match Pin::new(&mut future).poll(&mut cx) {
+            Poll::Ready(Ok(42)) => {}
+            other => panic!("expected Poll::Ready(Ok(42)), got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+
+use c_ares;
+
+/// The result of a successful host lookup.
+#[derive(Clone, Debug)]
+pub struct HostResults {
+    inner: c_ares::HostResults,
+}
+
+impl HostResults {
+    pub(crate) fn new(inner: c_ares::HostResults) -> HostResults {
+        HostResults { inner }
+    }
+
+    /// Returns the canonical hostname that was looked up.
+    pub fn hostname(&self) -> &str {
+        self.inner.hostname()
+    }
+
+    /// Returns the IP addresses found for this host.
+    pub fn addresses(&self) -> impl Iterator<Item = ::std::net::IpAddr> + '_ {
+        self.inner.addresses()
+    }
+
+    /// Returns the aliases found for this host.
+    pub fn aliases(&self) -> impl Iterator<Item = &str> {
+        self.inner.aliases()
+    }
+}
+
+impl fmt::Display for HostResults {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
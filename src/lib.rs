@@ -9,8 +9,9 @@
 //! Simply create a `Resolver`, and make your query - providing a callback to be called when the
 //! query completes.
 //!
-//! This crate also provides a `FutureResolver`.  Queries on this object return `futures::Future`
-//! objects, and don't use callbacks.
+//! This crate also provides a `FutureResolver`.  Queries on this object return
+//! `std::future::Future` objects that can be `.await`ed directly on whatever async runtime
+//! you're already using, and don't use callbacks.
 //!
 //! Additionally, this crate provides a `BlockingResolver`.  Usually if you're using `c-ares`, it's
 //! because you care about high-performance, asynchronous code.  But sometimes you'd just like to
@@ -31,16 +32,11 @@
 //! # Example
 //!
 //! ```rust
-//! extern crate c_ares_resolver;
-//! extern crate tokio_core;
-//!
-//! fn main() {
-//!     let resolver = c_ares_resolver::FutureResolver::new().unwrap();
-//!     let query = resolver.query_a("google.com");
-//!     let mut event_loop = tokio_core::reactor::Core::new().unwrap();
-//!     let result = event_loop.run(query).unwrap();
-//!     println!("{}", result);
-//! }
+//! # async fn example() {
+//! let resolver = c_ares_resolver::FutureResolver::new().unwrap();
+//! let result = resolver.query_a("google.com").await.unwrap();
+//! println!("{}", result);
+//! # }
 //! ```
 //!
 //! Further examples showing how to use the library can be found
@@ -48,9 +44,6 @@
 #![deny(missing_docs)]
 extern crate c_ares;
 extern crate futures;
-extern crate mio_extras;
-
-#[cfg(unix)]
 extern crate mio;
 
 #[cfg(windows)]
@@ -75,4 +68,4 @@ pub use error::Error;
 pub use futureresolver::{CAresFuture, FutureResolver};
 pub use host::HostResults;
 pub use nameinfo::NameInfoResult;
-pub use resolver::{Options, Resolver};
+pub use resolver::{Options, Resolver, SocketInterest};
@@ -0,0 +1,31 @@
+use std::fmt;
+
+use c_ares;
+
+/// The result of a successful name-info lookup.
+#[derive(Clone, Debug)]
+pub struct NameInfoResult {
+    inner: c_ares::NameInfoResult,
+}
+
+impl NameInfoResult {
+    pub(crate) fn new(inner: c_ares::NameInfoResult) -> NameInfoResult {
+        NameInfoResult { inner }
+    }
+
+    /// Returns the node (hostname) found, if any.
+    pub fn node(&self) -> Option<&str> {
+        self.inner.node()
+    }
+
+    /// Returns the service found, if any.
+    pub fn service(&self) -> Option<&str> {
+        self.inner.service()
+    }
+}
+
+impl fmt::Display for NameInfoResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
@@ -0,0 +1,336 @@
+use std::sync::{Arc, Mutex};
+
+use c_ares;
+
+use error::Error;
+use eventloop::EventLoop;
+use host::HostResults;
+use nameinfo::NameInfoResult;
+
+/// Options for configuring a `Resolver` or `FutureResolver`.
+///
+/// This just wraps the options provided by the underlying `c-ares` library - see its
+/// documentation for details.
+#[derive(Default)]
+pub struct Options {
+    inner: c_ares::Options,
+}
+
+impl Options {
+    /// Returns a fresh `Options`, on which no values are yet set.
+    pub fn new() -> Options {
+        Options {
+            inner: c_ares::Options::new(),
+        }
+    }
+
+    /// Set the number of milliseconds each attempt at a query should wait for a response before
+    /// retrying.
+    pub fn set_timeout(&mut self, timeout_ms: u32) -> &mut Self {
+        self.inner.set_timeout(timeout_ms);
+        self
+    }
+
+    /// Set the number of tries that will be made for a query before giving up.
+    pub fn set_tries(&mut self, tries: u32) -> &mut Self {
+        self.inner.set_tries(tries as i32);
+        self
+    }
+
+    /// Set a callback to be invoked whenever the set of file descriptors that the channel is
+    /// interested in changes.
+    ///
+    /// This is only useful in combination with `Resolver::with_options_for_manual_driving()`:
+    /// together, they let a caller embed the channel in their own event loop instead of using
+    /// the background thread that `Resolver::new()` spawns.
+    pub fn set_sock_state_callback<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: FnMut(c_ares::Socket, bool, bool) + Send + 'static,
+    {
+        self.inner.set_sock_state_callback(callback);
+        self
+    }
+
+    pub(crate) fn into_inner(self) -> c_ares::Options {
+        self.inner
+    }
+}
+
+/// A file descriptor (or, on Windows, a `SOCKET`) that the underlying `c-ares` channel is
+/// currently interested in, together with which directions it wants to be polled for.
+#[derive(Clone, Copy, Debug)]
+pub struct SocketInterest {
+    fd: c_ares::Socket,
+    readable: bool,
+    writable: bool,
+}
+
+impl SocketInterest {
+    /// The file descriptor in question.
+    pub fn fd(&self) -> c_ares::Socket {
+        self.fd
+    }
+
+    /// Whether `c-ares` wants to know when this descriptor becomes readable.
+    pub fn readable(&self) -> bool {
+        self.readable
+    }
+
+    /// Whether `c-ares` wants to know when this descriptor becomes writable.
+    pub fn writable(&self) -> bool {
+        self.writable
+    }
+}
+
+/// `Resolver` provides an asynchronous lookup API, issuing callbacks on completion.
+///
+/// By default, queries are resolved using a background thread, which drives a `mio`-based event
+/// loop on `Resolver`'s behalf - see the crate documentation for why this is convenient. For
+/// callers who'd rather embed the channel in an event loop of their own, see
+/// `with_options_for_manual_driving()`.
+pub struct Resolver {
+    ares_channel: Arc<Mutex<c_ares::Channel>>,
+    event_loop: Option<EventLoop>,
+}
+
+impl Resolver {
+    /// Create a new `Resolver`, using default `Options`.
+    pub fn new() -> Result<Resolver, Error> {
+        Resolver::with_options(Options::new())
+    }
+
+    /// Create a new `Resolver`, with the given `Options`.
+    pub fn with_options(options: Options) -> Result<Resolver, Error> {
+        let ares_channel = c_ares::Channel::new(options.into_inner())?;
+        let ares_channel = Arc::new(Mutex::new(ares_channel));
+        let event_loop = EventLoop::new(Arc::clone(&ares_channel))?;
+        let resolver = Resolver {
+            ares_channel,
+            event_loop: Some(event_loop),
+        };
+        Ok(resolver)
+    }
+
+    /// Create a new `Resolver`, with the given `Options`, without spawning a background thread
+    /// to drive it.
+    ///
+    /// Use this when you'd rather embed the `c-ares` channel in an event loop you already run
+    /// yourself. Register a callback with `Options::set_sock_state_callback()` beforehand to
+    /// learn when the set of interesting file descriptors changes, call `fds()` to find out
+    /// what they currently are, poll them yourself, and call `process_fd()` to report back
+    /// when they become readable or writable.
+    pub fn with_options_for_manual_driving(options: Options) -> Result<Resolver, Error> {
+        let ares_channel = c_ares::Channel::new(options.into_inner())?;
+        let resolver = Resolver {
+            ares_channel: Arc::new(Mutex::new(ares_channel)),
+            event_loop: None,
+        };
+        Ok(resolver)
+    }
+
+    /// Return the file descriptors that the underlying `c-ares` channel currently wants to be
+    /// polled on, along with the interest (readable, writable, or both) in each.
+    ///
+    /// Only meaningful for a `Resolver` built with `with_options_for_manual_driving()`.
+    pub fn fds(&self) -> Vec<SocketInterest> {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .fds()
+            .into_iter()
+            .map(|fd_info| SocketInterest {
+                fd: fd_info.fd(),
+                readable: fd_info.readable(),
+                writable: fd_info.writable(),
+            })
+            .collect()
+    }
+
+    /// Tell the channel that the given file descriptors are readable and/or writable.
+    ///
+    /// Pass `c_ares::SOCKET_BAD` for whichever of `read_fd`/`write_fd` isn't ready. Only
+    /// meaningful for a `Resolver` built with `with_options_for_manual_driving()`.
+    pub fn process_fd(&self, read_fd: c_ares::Socket, write_fd: c_ares::Socket) {
+        self.ares_channel.lock().unwrap().process_fd(read_fd, write_fd);
+    }
+
+    /// Return how long the caller should wait, at most, before calling `process_fd()` again
+    /// even if none of the descriptors from `fds()` have become ready.
+    ///
+    /// `c-ares` uses this to drive its own retry and timeout logic - without calling
+    /// `process_fd(SOCKET_BAD, SOCKET_BAD)` on roughly this cadence, a query against an
+    /// unresponsive server will never time out. Returns `None` if there's currently no
+    /// outstanding query to wait on. Only meaningful for a `Resolver` built with
+    /// `with_options_for_manual_driving()` - the background thread used by `new()`/
+    /// `with_options()` already takes care of this.
+    pub fn timeout(&self) -> Option<::std::time::Duration> {
+        self.ares_channel.lock().unwrap().timeout()
+    }
+
+    fn wakeup(&self) {
+        if let Some(ref event_loop) = self.event_loop {
+            event_loop.wakeup();
+        }
+    }
+
+    /// Cancel every query that is currently outstanding on this resolver's channel.
+    ///
+    /// Cancelled queries complete immediately, with an `Error::CAresError` wrapping
+    /// `c_ares::Error::ECANCELLED`. `c-ares` has no way to cancel a single query in
+    /// isolation - `ares_cancel()` cancels everything outstanding on the channel - so that's
+    /// also as fine-grained as this gets.
+    pub fn cancel(&self) {
+        self.ares_channel.lock().unwrap().cancel();
+    }
+
+    /// Initiate an A record lookup.
+    pub fn query_a<F>(&self, name: &str, callback: F)
+    where
+        F: FnOnce(Result<c_ares::AResults, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .query_a(name, move |result| callback(result.map_err(Error::from)));
+        self.wakeup();
+    }
+
+    /// Initiate an AAAA record lookup.
+    pub fn query_aaaa<F>(&self, name: &str, callback: F)
+    where
+        F: FnOnce(Result<c_ares::AAAAResults, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .query_aaaa(name, move |result| callback(result.map_err(Error::from)));
+        self.wakeup();
+    }
+
+    /// Initiate a CNAME record lookup.
+    pub fn query_cname<F>(&self, name: &str, callback: F)
+    where
+        F: FnOnce(Result<c_ares::CNameResults, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .query_cname(name, move |result| callback(result.map_err(Error::from)));
+        self.wakeup();
+    }
+
+    /// Initiate an MX record lookup.
+    pub fn query_mx<F>(&self, name: &str, callback: F)
+    where
+        F: FnOnce(Result<c_ares::MXResults, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .query_mx(name, move |result| callback(result.map_err(Error::from)));
+        self.wakeup();
+    }
+
+    /// Initiate a PTR record lookup.
+    pub fn query_ptr<F>(&self, name: &str, callback: F)
+    where
+        F: FnOnce(Result<c_ares::PTRResults, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .query_ptr(name, move |result| callback(result.map_err(Error::from)));
+        self.wakeup();
+    }
+
+    /// Initiate a TXT record lookup.
+    pub fn query_txt<F>(&self, name: &str, callback: F)
+    where
+        F: FnOnce(Result<c_ares::TXTResults, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .query_txt(name, move |result| callback(result.map_err(Error::from)));
+        self.wakeup();
+    }
+
+    /// Initiate a raw DNS query for `name`, for the given `dns_class` and `query_type`.
+    ///
+    /// This is a lower-level entry point than the other `query_xxx` methods: instead of parsing
+    /// the answer into a record-type-specific result, it hands back the unparsed wire-format DNS
+    /// response (the `c-ares` `abuf`/`alen` pair). This is useful for querying record types that
+    /// this crate doesn't have a typed wrapper for - the caller can parse the answer themselves
+    /// with a DNS message crate of their choice.
+    pub fn query_raw<F>(&self, name: &str, dns_class: c_ares::DNSClass, query_type: c_ares::QueryType, callback: F)
+    where
+        F: FnOnce(Result<Vec<u8>, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .query_raw(name, dns_class, query_type, move |result| {
+                callback(result.map(|abuf| abuf.to_vec()).map_err(Error::from))
+            });
+        self.wakeup();
+    }
+
+    /// As with `query_raw()`, but makes a call to `ares_search()` rather than `ares_query()`.
+    pub fn search_raw<F>(&self, name: &str, dns_class: c_ares::DNSClass, query_type: c_ares::QueryType, callback: F)
+    where
+        F: FnOnce(Result<Vec<u8>, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .search_raw(name, dns_class, query_type, move |result| {
+                callback(result.map(|abuf| abuf.to_vec()).map_err(Error::from))
+            });
+        self.wakeup();
+    }
+
+    /// Look up the A and AAAA records for the given host name.
+    pub fn get_host_by_name<F>(&self, name: &str, family: c_ares::AddressFamily, callback: F)
+    where
+        F: FnOnce(Result<HostResults, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .get_host_by_name(name, family, move |result| {
+                callback(result.map(HostResults::new).map_err(Error::from))
+            });
+        self.wakeup();
+    }
+
+    /// Address-to-nodename translation, as per RFC 3493, section 6.2.
+    pub fn get_name_info<F>(&self, address: ::std::net::SocketAddr, flags: i32, callback: F)
+    where
+        F: FnOnce(Result<NameInfoResult, Error>) + Send + 'static,
+    {
+        self.ares_channel
+            .lock()
+            .unwrap()
+            .get_name_info(address, flags, move |result| {
+                callback(result.map(NameInfoResult::new).map_err(Error::from))
+            });
+        self.wakeup();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn socket_interest_exposes_what_it_was_built_with() {
+        let interest = SocketInterest {
+            fd: 7,
+            readable: true,
+            writable: false,
+        };
+        assert_eq!(interest.fd(), 7);
+        assert!(interest.readable());
+        assert!(!interest.writable());
+    }
+}
@@ -0,0 +1,81 @@
+//! Platform glue for driving a `c-ares` channel from a `mio` event loop on Unix.
+use std::collections::HashMap;
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+use c_ares;
+use mio;
+use mio::unix::EventedFd;
+
+/// The type that `c-ares` uses to identify a socket on this platform.
+pub type Socket = RawFd;
+
+/// How long `EventLoop`'s background thread should block waiting for fd readiness before
+/// falling back to a timer-driven `process_fd(SOCKET_BAD, SOCKET_BAD)` call.
+///
+/// On Unix we get real readiness notifications from `poll.register()`/`reregister()` below, so
+/// there's no need to poll more often than `c-ares` itself asks for.
+pub fn poll_timeout(channel_timeout: Duration) -> Duration {
+    channel_timeout
+}
+
+/// Report the readiness events `mio` gave us back to `c-ares`, or - if there weren't any - drive
+/// its timeout/retry handling instead.
+///
+/// `activity` is `(fd, readable, writable)` for every non-wakeup event from this iteration's
+/// `poll.poll()`; `_channel_timeout` is unused here since the real readiness notifications from
+/// `poll.register()`/`reregister()` below mean we never need to poll more often than that anyway.
+pub fn drive(channel: &c_ares::Channel, activity: &[(usize, bool, bool)], _channel_timeout: Duration) {
+    if activity.is_empty() {
+        channel.process_fd(c_ares::SOCKET_BAD, c_ares::SOCKET_BAD);
+        return;
+    }
+    for &(fd, readable, writable) in activity {
+        let fd = fd as RawFd;
+        let read_fd = if readable { fd } else { c_ares::SOCKET_BAD };
+        let write_fd = if writable { fd } else { c_ares::SOCKET_BAD };
+        channel.process_fd(read_fd, write_fd);
+    }
+}
+
+pub fn refresh_registrations(
+    poll: &mio::Poll,
+    channel: &c_ares::Channel,
+    registrations: &mut HashMap<Socket, mio::Token>,
+    token_base: usize,
+) {
+    let active: Vec<(RawFd, bool, bool)> = channel
+        .fds()
+        .into_iter()
+        .map(|fd_info| (fd_info.fd(), fd_info.readable(), fd_info.writable()))
+        .collect();
+
+    registrations.retain(|fd, token| {
+        if active.iter().any(|&(active_fd, _, _)| active_fd == *fd) {
+            true
+        } else {
+            let _ = poll.deregister(&EventedFd(fd));
+            let _ = token;
+            false
+        }
+    });
+
+    for &(fd, readable, writable) in active.iter() {
+        let mut interest = mio::Ready::empty();
+        if readable {
+            interest |= mio::Ready::readable();
+        }
+        if writable {
+            interest |= mio::Ready::writable();
+        }
+        // The token is derived from the fd itself, so that `EventLoop` can map an event's token
+        // straight back to the fd it is for.
+        let token = mio::Token(token_base + fd as usize);
+        if let Some(existing) = registrations.get(&fd) {
+            let _ = poll.reregister(&EventedFd(&fd), *existing, interest, mio::PollOpt::edge());
+        } else {
+            let _ = poll.register(&EventedFd(&fd), token, interest, mio::PollOpt::edge());
+            registrations.insert(fd, token);
+        }
+    }
+}
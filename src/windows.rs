@@ -0,0 +1,118 @@
+//! Platform glue for driving a `c-ares` channel from a `mio` event loop on Windows.
+//!
+//! `mio` 0.6's Windows backend is built on IOCP, which (unlike epoll/kqueue on Unix) can only
+//! deliver readiness notifications for sockets that it created itself - there's no supported
+//! way to hand it an arbitrary externally-owned `SOCKET` and ask to be told when it's readable.
+//! That means `refresh_registrations()` below never actually registers anything with `poll` -
+//! it only keeps its `registrations` map in step with the sockets `c-ares` currently cares about.
+//!
+//! Instead, `drive()` polls those sockets for real readiness itself, via `WSAPoll()` - the
+//! Windows equivalent of `poll(2)` - and only reports back to `c-ares` the sockets that
+//! `WSAPoll()` says are actually ready, the same as `unix.rs` does with real `mio` events. If
+//! `WSAPoll()` times out with nothing ready, that's also `c-ares`'s cue to drive its own
+//! retry/timeout logic, via `process_fd(SOCKET_BAD, SOCKET_BAD)`.
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+
+use c_ares;
+use mio;
+use winapi::um::winsock2::{WSAPoll, POLLRDNORM, POLLWRNORM, SOCKET, WSAPOLLFD};
+
+/// The type that `c-ares` uses to identify a socket on this platform.
+pub type Socket = SOCKET;
+
+/// The longest a single `WSAPoll()` call in `drive()` will block before we come back around to
+/// check the channel's own fd list and the shutdown flag again.
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long `EventLoop`'s background thread should block in `poll.poll()` before moving on to
+/// `drive()`, which does the real waiting on this platform - see the module documentation.
+pub fn poll_timeout(_channel_timeout: Duration) -> Duration {
+    Duration::from_millis(0)
+}
+
+/// Poll the sockets `c-ares` currently cares about for real readiness, via `WSAPoll()`, and
+/// report whichever of them are ready back to the channel. If none are, drive the channel's
+/// timeout/retry handling instead, the same as a Unix poll iteration that sees no fd activity.
+///
+/// `activity` is unused here - `mio` never has real events to report on this platform, since it
+/// can't watch externally-owned sockets (see the module documentation).
+pub fn drive(channel: &c_ares::Channel, _activity: &[(usize, bool, bool)], channel_timeout: Duration) {
+    let fds = channel.fds();
+    if fds.is_empty() {
+        channel.process_fd(c_ares::SOCKET_BAD, c_ares::SOCKET_BAD);
+        return;
+    }
+
+    let mut poll_fds: Vec<WSAPOLLFD> = fds
+        .into_iter()
+        .map(|fd_info| {
+            let mut events = 0;
+            if fd_info.readable() {
+                events |= POLLRDNORM;
+            }
+            if fd_info.writable() {
+                events |= POLLWRNORM;
+            }
+            WSAPOLLFD {
+                fd: fd_info.fd() as SOCKET,
+                events: events as i16,
+                revents: 0,
+            }
+        })
+        .collect();
+
+    let timeout_ms = cmp_min_millis(channel_timeout, MAX_POLL_INTERVAL);
+    let ready = unsafe { WSAPoll(poll_fds.as_mut_ptr(), poll_fds.len() as u32, timeout_ms) };
+
+    if ready <= 0 {
+        // Either nothing was ready before the timeout, or `WSAPoll()` itself failed - either
+        // way, fall back to driving `c-ares`'s own timeout/retry logic.
+        if ready < 0 {
+            let _ = io::Error::last_os_error();
+        }
+        channel.process_fd(c_ares::SOCKET_BAD, c_ares::SOCKET_BAD);
+        return;
+    }
+
+    for poll_fd in &poll_fds {
+        if poll_fd.revents == 0 {
+            continue;
+        }
+        let readable = poll_fd.revents & POLLRDNORM != 0;
+        let writable = poll_fd.revents & POLLWRNORM != 0;
+        let read_fd = if readable { poll_fd.fd } else { c_ares::SOCKET_BAD };
+        let write_fd = if writable { poll_fd.fd } else { c_ares::SOCKET_BAD };
+        channel.process_fd(read_fd, write_fd);
+    }
+}
+
+fn cmp_min_millis(a: Duration, b: Duration) -> i32 {
+    ::std::cmp::min(a, b).as_millis() as i32
+}
+
+/// This does not register anything with `poll` - see the module documentation for why `mio`
+/// can't help us here on Windows. It only keeps `registrations` in step with the sockets
+/// `c-ares` currently cares about. `Resolver::fds()` does not read this map; it asks the channel
+/// directly.
+pub fn refresh_registrations(
+    _poll: &mio::Poll,
+    channel: &c_ares::Channel,
+    registrations: &mut HashMap<Socket, mio::Token>,
+    token_base: usize,
+) {
+    let active: Vec<(SOCKET, bool, bool)> = channel
+        .fds()
+        .into_iter()
+        .map(|fd_info| (fd_info.fd(), fd_info.readable(), fd_info.writable()))
+        .collect();
+
+    registrations.retain(|socket, _| active.iter().any(|&(active_socket, _, _)| active_socket == *socket));
+
+    for (index, &(socket, _readable, _writable)) in active.iter().enumerate() {
+        registrations
+            .entry(socket)
+            .or_insert_with(|| mio::Token(token_base + index));
+    }
+}